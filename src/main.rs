@@ -1,12 +1,125 @@
+use async_compression::tokio::write::ZstdEncoder;
 use aws_config::meta::region::RegionProviderChain;
+use aws_sdk_s3::primitives::{ByteStream, Length};
+use aws_sdk_s3::types::{ChecksumMode, CompletedMultipartUpload, CompletedPart};
 use aws_sdk_s3::Client;
+use base64::Engine;
 use clap::Parser;
-use std::path::PathBuf;
+use futures::stream::FuturesUnordered;
+use futures::StreamExt;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::cmp::min;
+use std::collections::HashSet;
+use std::io::SeekFrom;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
 use tokio::fs::File;
+use tokio::io::AsyncReadExt;
+use tokio::io::AsyncSeekExt;
 use tokio::io::AsyncWriteExt;
 use tokio::io::BufWriter;
+use tokio::sync::Mutex;
+use tokio::sync::Semaphore;
 use tokio::task;
-use tracing::trace;
+use tracing::{trace, warn};
+
+mod ring_buffer;
+
+// Bytes in flight between the network-receive task and the disk-write task for
+// the single-stream download path.
+const RING_BUFFER_CAPACITY: usize = 4 * 1024 * 1024;
+
+// S3 requires every part but the last to be at least 5 MiB.
+const MIN_UPLOAD_PART_SIZE: u64 = 5 * 1024 * 1024;
+// Comfortably above the minimum so we don't balloon the part count on large files.
+const UPLOAD_CHUNK_SIZE: u64 = 8 * 1024 * 1024;
+const BASE_RETRY_DELAY: Duration = Duration::from_millis(200);
+
+// Retries `attempt_fn` on retryable errors with full-jitter exponential backoff
+// (`BASE_RETRY_DELAY * 2^attempt`, slept for a random duration in `0..=that`), up
+// to `max_retries` times. Non-retryable errors and the final exhausted attempt are
+// returned with context naming what was being attempted.
+async fn with_retries<T, F, Fut>(
+    max_retries: u32,
+    description: &str,
+    mut attempt_fn: F,
+) -> Result<T, anyhow::Error>
+where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = Result<T, anyhow::Error>>,
+{
+    let mut attempt = 0_u32;
+    loop {
+        match attempt_fn().await {
+            Ok(value) => return Ok(value),
+            Err(err) if attempt < max_retries && is_retryable(&err) => {
+                let delay = backoff_with_jitter(attempt);
+                trace!("{description} failed on attempt {attempt}: {err}; retrying in {delay:?}");
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+            }
+            Err(err) => {
+                return Err(
+                    err.context(format!("{description} failed after {} attempt(s)", attempt + 1))
+                )
+            }
+        }
+    }
+}
+
+fn backoff_with_jitter(attempt: u32) -> Duration {
+    let delay = BASE_RETRY_DELAY * 2_u32.saturating_pow(attempt);
+    let jitter_ms = rand::thread_rng().gen_range(0..=delay.as_millis() as u64);
+    Duration::from_millis(jitter_ms)
+}
+
+// The SDK's error enums are generic per operation (`SdkError<GetObjectError, _>`,
+// `SdkError<HeadObjectError, _>`, ...), and `with_retries` is shared across more
+// than one of them, so there's no single concrete error type to match on here without threading
+// a type parameter through every call site. Instead we classify retryability from
+// the error text. This is necessarily best-effort: it can both false-positive (an
+// unrelated error that happens to mention "500") and false-negative (a service error
+// code whose `Display` wording doesn't match any token below). Bare digit codes are
+// checked as standalone tokens, not raw substrings, so e.g. "500" doesn't match
+// inside an unrelated number like a byte offset or part number.
+fn is_retryable(err: &anyhow::Error) -> bool {
+    let message = err.to_string().to_lowercase();
+    message.contains("timeout")
+        || message.contains("timed out")
+        || message.contains("connection reset")
+        || message.contains("connection closed")
+        || message.contains("broken pipe")
+        || message.contains("slow down")
+        || message.contains("slowdown")
+        || message.contains("internal error")
+        || message.contains("internalerror")
+        || message.contains("service unavailable")
+        || message.contains("request timeout")
+        || ["500", "502", "503", "504"]
+            .iter()
+            .any(|code| contains_status_code(&message, code))
+}
+
+/// True if `code` (e.g. "503") appears in `message` as a standalone token, i.e. not
+/// immediately preceded or followed by another digit. Guards against matching a
+/// status code inside an unrelated number (a byte range, part number, ...).
+fn contains_status_code(message: &str, code: &str) -> bool {
+    message.match_indices(code).any(|(start, matched)| {
+        let before_is_digit = message[..start]
+            .chars()
+            .next_back()
+            .is_some_and(|c| c.is_ascii_digit());
+        let end = start + matched.len();
+        let after_is_digit = message[end..]
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_ascii_digit());
+        !before_is_digit && !after_is_digit
+    })
+}
 
 #[derive(Debug, Parser, Clone)]
 struct Opt {
@@ -18,6 +131,72 @@ struct Opt {
     destination: PathBuf,
     #[structopt(long)]
     multipart: bool,
+    /// Upload `destination` to `bucket`/`object` instead of downloading.
+    #[structopt(long)]
+    upload: bool,
+    /// Maximum number of multipart parts to transfer concurrently.
+    #[structopt(long, default_value_t = 8)]
+    concurrency: usize,
+    /// Maximum number of retries for a retryable request before giving up.
+    #[structopt(long, default_value_t = 5)]
+    max_retries: u32,
+    /// Fetch every key in `--job-file` between `--start-index` and `--end-index`
+    /// and aggregate them into a single zstd-compressed segment file.
+    #[structopt(long)]
+    aggregate: bool,
+    /// Newline-separated file of object keys to aggregate.
+    #[structopt(long)]
+    job_file: Option<PathBuf>,
+    /// First index (inclusive) into the job file to aggregate.
+    #[structopt(long)]
+    start_index: Option<u64>,
+    /// Last index (exclusive) into the job file to aggregate.
+    #[structopt(long)]
+    end_index: Option<u64>,
+    /// Directory the aggregate segment file is written into.
+    #[structopt(long, default_value = ".")]
+    output_dir: PathBuf,
+    /// Verify the downloaded bytes against the object's server-side checksum/ETag.
+    #[structopt(long)]
+    verify: bool,
+    /// Resume an interrupted download instead of restarting it from scratch.
+    #[structopt(long)]
+    resume: bool,
+}
+
+// Written alongside `destination` (as `destination` + `.part`) while a resumable
+// download is in progress, and removed on success. Lets `--resume` tell whether a
+// partial destination file still matches the remote object, and (for multipart
+// downloads) which parts it already contains.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+struct TransferMetadata {
+    e_tag: Option<String>,
+    last_modified_secs: Option<i64>,
+    object_size: u64,
+    completed_part_offsets: Vec<u64>,
+}
+
+fn sidecar_path(destination: &Path) -> PathBuf {
+    let mut name = destination.as_os_str().to_owned();
+    name.push(".part");
+    PathBuf::from(name)
+}
+
+async fn write_transfer_metadata(
+    path: &Path,
+    metadata: &TransferMetadata,
+) -> Result<(), anyhow::Error> {
+    let json = serde_json::to_vec_pretty(metadata)?;
+    tokio::fs::write(path, json).await?;
+    Ok(())
+}
+
+async fn read_transfer_metadata(path: &Path) -> Result<Option<TransferMetadata>, anyhow::Error> {
+    match tokio::fs::read(path).await {
+        Ok(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err.into()),
+    }
 }
 
 // Use normal API to download a file and write to disk
@@ -26,26 +205,291 @@ async fn get_object(client: Client, opt: Opt) -> Result<usize, anyhow::Error> {
     trace!("object:      {}", opt.object);
     trace!("destination: {}", opt.destination.display());
 
-    let mut file = File::create(opt.destination.clone()).await?;
+    let metadata_path = sidecar_path(&opt.destination);
 
-    let mut object = client
-        .get_object()
-        .bucket(opt.bucket)
-        .key(opt.object)
-        .send()
+    let head = with_retries(opt.max_retries, "head_object", || async {
+        client
+            .head_object()
+            .bucket(&opt.bucket)
+            .key(&opt.object)
+            .send()
+            .await
+            .map_err(anyhow::Error::from)
+    })
+    .await?;
+    let last_modified_secs = head.last_modified.map(|ts| ts.secs());
+
+    let mut resume_from = 0_u64;
+    if opt.resume {
+        if let Some(saved) = read_transfer_metadata(&metadata_path).await? {
+            if saved.e_tag == head.e_tag && saved.last_modified_secs == last_modified_secs {
+                if let Ok(existing) = tokio::fs::metadata(&opt.destination).await {
+                    resume_from = existing.len();
+                }
+            }
+        }
+    }
+
+    if opt.resume && resume_from == 0 {
+        write_transfer_metadata(
+            &metadata_path,
+            &TransferMetadata {
+                e_tag: head.e_tag.clone(),
+                last_modified_secs,
+                object_size: head.content_length.unwrap_or_default() as u64,
+                completed_part_offsets: Vec::new(),
+            },
+        )
+        .await?;
+    }
+
+    let mut file = tokio::fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(resume_from > 0)
+        .truncate(resume_from == 0)
+        .open(&opt.destination)
         .await?;
 
-    let mut byte_count = 0_usize;
-    while let Some(bytes) = object.body.try_next().await? {
-        let bytes_len = bytes.len();
-        file.write_all(&bytes).await?;
-        trace!("Intermediate write of {bytes_len}");
-        byte_count += bytes_len;
+    let mut request = client.get_object().bucket(&opt.bucket).key(&opt.object);
+    if resume_from > 0 {
+        trace!("resuming {} from byte {resume_from}", opt.object);
+        request = request.range(format!("bytes={resume_from}-"));
+    }
+    if opt.verify {
+        request = request.checksum_mode(ChecksumMode::Enabled);
+    }
+    let object = request.send().await?;
+    let expected_crc32c = object.checksum_crc32_c.clone();
+    let mut body = object.body;
+
+    // Decouple network receive from the disk write: one future pushes chunks as
+    // they arrive off the wire into the ring buffer, while the other drains it
+    // with large sequential `write_all`s, instead of writing (and hashing) each
+    // network chunk inline before the next one is read. Both futures are driven
+    // concurrently by `tokio::join!` on the current task.
+    let (producer, consumer) = ring_buffer::ring_buffer(RING_BUFFER_CAPACITY)?;
+
+    let receive = async {
+        while let Some(bytes) = body.try_next().await? {
+            let mut remaining = &bytes[..];
+            while !remaining.is_empty() {
+                let chunk_len = remaining.len().min(RING_BUFFER_CAPACITY);
+                let slot = producer.reserve(chunk_len).await;
+                slot.copy_from_slice(&remaining[..chunk_len]);
+                producer.commit(chunk_len);
+                remaining = &remaining[chunk_len..];
+            }
+        }
+        producer.close();
+        Ok::<(), anyhow::Error>(())
+    };
+
+    let write = async {
+        let mut byte_count = resume_from as usize;
+        let mut crc = 0_u32;
+        while let Some(bytes) = consumer.pop(RING_BUFFER_CAPACITY).await {
+            let bytes_len = bytes.len();
+            if opt.verify && resume_from == 0 {
+                crc = crc32c::crc32c_append(crc, bytes);
+            }
+            file.write_all(bytes).await?;
+            consumer.commit(bytes_len);
+            trace!("Intermediate write of {bytes_len}");
+            byte_count += bytes_len;
+        }
+        Ok::<(usize, u32), anyhow::Error>((byte_count, crc))
+    };
+
+    let (receive_result, write_result) = tokio::join!(receive, write);
+    receive_result?;
+    let (byte_count, crc) = write_result?;
+
+    if opt.verify {
+        if resume_from == 0 {
+            verify_single_stream_checksum(&opt.object, crc, expected_crc32c.as_deref())?;
+        } else {
+            // The CRC32C above only covers bytes received this run; the resumed prefix
+            // was never re-read, so there's nothing honest to check it against. Say so
+            // instead of silently skipping, matching the no-checksum-available warning
+            // in `verify_single_stream_checksum`.
+            warn!(
+                "skipping integrity verification for {}: --resume reused {resume_from} \
+                 pre-existing bytes that were not re-hashed",
+                opt.object
+            );
+        }
     }
 
+    let _ = tokio::fs::remove_file(&metadata_path).await;
+
     Ok(byte_count)
 }
 
+fn verify_single_stream_checksum(
+    key: &str,
+    actual_crc: u32,
+    expected_crc_b64: Option<&str>,
+) -> Result<(), anyhow::Error> {
+    // Most objects in the wild were never uploaded with a CRC32C checksum attached,
+    // so its absence isn't evidence of corruption — just of nothing to compare
+    // against. Only an actual mismatch is a verification failure.
+    let Some(expected) = expected_crc_b64 else {
+        warn!("object {key} has no CRC32C checksum; skipping integrity verification");
+        return Ok(());
+    };
+    let actual = base64::engine::general_purpose::STANDARD.encode(actual_crc.to_be_bytes());
+
+    anyhow::ensure!(
+        actual == expected,
+        "checksum mismatch for {key}: expected CRC32C {expected}, got {actual}"
+    );
+
+    Ok(())
+}
+
+// Mirrors the SDK's upload-file-multipart example: create an upload, ship each
+// part concurrently, then complete (or abort, on any failure) the upload.
+async fn put_object_multipart(client: Client, opt: Opt) -> Result<usize, anyhow::Error> {
+    debug_assert!(UPLOAD_CHUNK_SIZE >= MIN_UPLOAD_PART_SIZE);
+
+    trace!("bucket: {}", opt.bucket);
+    trace!("object: {}", opt.object);
+    trace!("source: {}", opt.destination.display());
+
+    let file_size = tokio::fs::metadata(&opt.destination).await?.len();
+
+    let create = client
+        .create_multipart_upload()
+        .bucket(&opt.bucket)
+        .key(&opt.object)
+        .send()
+        .await?;
+    let upload_id = create
+        .upload_id
+        .ok_or_else(|| anyhow::anyhow!("create_multipart_upload response missing upload_id"))?;
+
+    match upload_parts(&client, &opt, &upload_id, file_size).await {
+        Ok(mut parts) => {
+            parts.sort_by_key(|part| part.part_number());
+
+            client
+                .complete_multipart_upload()
+                .bucket(&opt.bucket)
+                .key(&opt.object)
+                .upload_id(&upload_id)
+                .multipart_upload(
+                    CompletedMultipartUpload::builder()
+                        .set_parts(Some(parts))
+                        .build(),
+                )
+                .send()
+                .await?;
+
+            Ok(file_size as usize)
+        }
+        Err(err) => {
+            // Don't leave a dangling upload (and its storage charges) behind.
+            client
+                .abort_multipart_upload()
+                .bucket(&opt.bucket)
+                .key(&opt.object)
+                .upload_id(&upload_id)
+                .send()
+                .await?;
+
+            Err(err)
+        }
+    }
+}
+
+async fn upload_parts(
+    client: &Client,
+    opt: &Opt,
+    upload_id: &str,
+    file_size: u64,
+) -> Result<Vec<CompletedPart>, anyhow::Error> {
+    let semaphore = Arc::new(Semaphore::new(opt.concurrency));
+    let mut in_flight = FuturesUnordered::new();
+
+    let mut part_number = 1_i32;
+    let mut start = 0_u64;
+    while start < file_size {
+        let end = min(start + UPLOAD_CHUNK_SIZE, file_size);
+
+        let client = client.clone();
+        let opt = opt.clone();
+        let upload_id = upload_id.to_owned();
+        let semaphore = semaphore.clone();
+
+        in_flight.push(task::spawn(async move {
+            let _permit = semaphore.acquire_owned().await?;
+            upload_part(client, opt, upload_id, part_number, start, end).await
+        }));
+
+        part_number += 1;
+        start = end;
+    }
+
+    // Drain every spawned task before surfacing an error, so a failed part
+    // doesn't leave its siblings still uploading (and potentially completing)
+    // after the caller has already told S3 to abort the upload.
+    let mut parts = Vec::new();
+    let mut first_error = None;
+    while let Some(result) = in_flight.next().await {
+        match result.map_err(anyhow::Error::from).and_then(|part| part) {
+            Ok(part) => parts.push(part),
+            Err(err) => {
+                first_error.get_or_insert(err);
+            }
+        }
+    }
+
+    if let Some(err) = first_error {
+        return Err(err);
+    }
+
+    Ok(parts)
+}
+
+async fn upload_part(
+    client: Client,
+    opt: Opt,
+    upload_id: String,
+    part_number: i32,
+    start: u64,
+    end: u64,
+) -> Result<CompletedPart, anyhow::Error> {
+    let body = ByteStream::read_from()
+        .path(&opt.destination)
+        .offset(start)
+        .length(Length::Exact(end - start))
+        .build()
+        .await?;
+
+    let response = client
+        .upload_part()
+        .bucket(&opt.bucket)
+        .key(&opt.object)
+        .upload_id(&upload_id)
+        .part_number(part_number)
+        .body(body)
+        .send()
+        .await?;
+
+    let e_tag = response
+        .e_tag
+        .ok_or_else(|| anyhow::anyhow!("upload_part response for part {part_number} missing ETag"))?;
+
+    Ok(CompletedPart::builder()
+        .e_tag(e_tag)
+        .part_number(part_number)
+        .build())
+}
+
+// Downloads with bounded concurrency and constant memory: at most `opt.concurrency`
+// parts are in flight at once, and each part streams straight to its absolute offset
+// in the (preallocated) destination file instead of buffering the whole part in RAM.
 async fn get_object_multipart(client: Client, opt: Opt) -> Result<usize, anyhow::Error> {
     // Lets use 10 MegaByte chunks
     let chunk_size = 10 * 1024 * 1024;
@@ -54,30 +498,104 @@ async fn get_object_multipart(client: Client, opt: Opt) -> Result<usize, anyhow:
     trace!("object:      {}", opt.object);
     trace!("destination: {}", opt.destination.display());
 
-    let file = File::create(opt.destination.clone()).await?;
-    let mut writer = BufWriter::new(file);
-
     // Get the object metadat so we can get the size
-    let object_metadata = client
-        .head_object()
-        .bucket(&opt.bucket)
-        .key(&opt.object)
-        .send()
-        .await?;
+    let object_metadata = with_retries(opt.max_retries, "head_object", || async {
+        client
+            .head_object()
+            .bucket(&opt.bucket)
+            .key(&opt.object)
+            .send()
+            .await
+            .map_err(anyhow::Error::from)
+    })
+    .await?;
 
     let object_size = object_metadata.content_length.unwrap() as u64;
+    let last_modified_secs = object_metadata.last_modified.map(|ts| ts.secs());
+
+    let metadata_path = sidecar_path(&opt.destination);
+
+    let mut completed_part_offsets = HashSet::new();
+    let mut reuse_existing_file = false;
+    if opt.resume {
+        if let Some(saved) = read_transfer_metadata(&metadata_path).await? {
+            if saved.e_tag == object_metadata.e_tag
+                && saved.last_modified_secs == last_modified_secs
+                && saved.object_size == object_size
+                && tokio::fs::metadata(&opt.destination).await.is_ok()
+            {
+                completed_part_offsets = saved.completed_part_offsets.into_iter().collect();
+                reuse_existing_file = true;
+            }
+        }
+    }
+
+    let file = if reuse_existing_file {
+        tokio::fs::OpenOptions::new()
+            .write(true)
+            .open(&opt.destination)
+            .await?
+    } else {
+        let file = File::create(opt.destination.clone()).await?;
+        file.set_len(object_size).await?;
+        file
+    };
+    let file = Arc::new(Mutex::new(file));
+
+    let progress = Arc::new(Mutex::new(TransferMetadata {
+        e_tag: object_metadata.e_tag.clone(),
+        last_modified_secs,
+        object_size,
+        completed_part_offsets: completed_part_offsets.iter().copied().collect(),
+    }));
+    if opt.resume {
+        write_transfer_metadata(&metadata_path, &*progress.lock().await).await?;
+    }
+
+    let semaphore = Arc::new(Semaphore::new(opt.concurrency));
+    let mut in_flight = FuturesUnordered::new();
+
+    // Tracks bytes actually confirmed present across both this run's freshly
+    // downloaded parts and any parts `--resume` is trusting from a prior run, so
+    // `--verify` checks what's really on disk instead of the preallocated
+    // `object_size` the file was `set_len`'d to up front.
+    let total_bytes = Arc::new(AtomicU64::new(0));
 
-    // Create a list of ranges to download
-    let mut tasks = Vec::new();
     let mut start: u64 = 0;
     let mut end: u64 = chunk_size;
-    while start < object_size as u64 {
-        tasks.push(task::spawn(download_part(
-            client.clone(),
-            opt.clone(),
-            start,
-            end,
-        )));
+    while start < object_size {
+        let part_len = end - start + 1;
+
+        if completed_part_offsets.contains(&start) {
+            trace!("skipping already-downloaded part at offset {start}");
+            // `end` (and so `part_len`) is clamped to `object_size` for the final part,
+            // one past the last valid inclusive byte index, so `part_len` alone would
+            // overcount that part by one versus what S3 actually returns for it.
+            total_bytes.fetch_add(part_len.min(object_size - start), Ordering::Relaxed);
+        } else {
+            let client = client.clone();
+            let opt = opt.clone();
+            let file = file.clone();
+            let semaphore = semaphore.clone();
+            let progress = progress.clone();
+            let metadata_path = metadata_path.clone();
+            let total_bytes = total_bytes.clone();
+
+            in_flight.push(task::spawn(async move {
+                let _permit = semaphore.acquire_owned().await?;
+                let bytes_written = download_part(client, opt, start, end, file).await?;
+                total_bytes.fetch_add(bytes_written as u64, Ordering::Relaxed);
+
+                if opt.resume {
+                    let mut progress = progress.lock().await;
+                    progress.completed_part_offsets.push(start);
+                    write_transfer_metadata(&metadata_path, &progress).await?;
+                }
+
+                Ok::<(), anyhow::Error>(())
+            }));
+        }
+
         start = end + 1;
         end += chunk_size;
         if end > object_size {
@@ -85,14 +603,79 @@ async fn get_object_multipart(client: Client, opt: Opt) -> Result<usize, anyhow:
         }
     }
 
-    let mut byte_count = 0;
-    for task in tasks {
-        let bytes = task.await??;
-        writer.write_all(&bytes).await?;
-        byte_count += bytes.len();
+    while let Some(result) = in_flight.next().await {
+        result??;
     }
 
-    Ok(byte_count)
+    if opt.verify {
+        verify_multipart_download(&client, &opt, object_size, total_bytes.load(Ordering::Relaxed))
+            .await?;
+    }
+
+    let _ = tokio::fs::remove_file(&metadata_path).await;
+
+    Ok(object_size as usize)
+}
+
+// Multipart ETags aren't a simple content MD5 once an object has more than one part,
+// so verification here is necessarily weaker than the single-stream CRC32C check: we
+// always confirm the total size, and only compare MD5s when the ETag tells us the
+// object was a single-part (plain MD5) upload.
+async fn verify_multipart_download(
+    client: &Client,
+    opt: &Opt,
+    expected_size: u64,
+    actual_size: u64,
+) -> Result<(), anyhow::Error> {
+    anyhow::ensure!(
+        expected_size == actual_size,
+        "size mismatch for {}: expected {expected_size} bytes, wrote {actual_size}",
+        opt.object
+    );
+
+    let metadata = with_retries(opt.max_retries, "head_object (verify)", || async {
+        client
+            .head_object()
+            .bucket(&opt.bucket)
+            .key(&opt.object)
+            .send()
+            .await
+            .map_err(anyhow::Error::from)
+    })
+    .await?;
+
+    let Some(etag) = metadata.e_tag else {
+        return Ok(());
+    };
+    let etag = etag.trim_matches('"');
+    if etag.contains('-') {
+        // Multipart-upload ETag: not a content MD5, nothing more we can check.
+        return Ok(());
+    }
+
+    let actual_md5 = md5_of_file(&opt.destination).await?;
+    anyhow::ensure!(
+        etag.eq_ignore_ascii_case(&actual_md5),
+        "checksum mismatch for {}: expected md5 {etag}, got {actual_md5}",
+        opt.object
+    );
+
+    Ok(())
+}
+
+async fn md5_of_file(path: &Path) -> Result<String, anyhow::Error> {
+    let mut file = File::open(path).await?;
+    let mut context = md5::Context::new();
+    let mut buf = vec![0_u8; 1024 * 1024];
+    loop {
+        let bytes_read = file.read(&mut buf).await?;
+        if bytes_read == 0 {
+            break;
+        }
+        context.consume(&buf[..bytes_read]);
+    }
+
+    Ok(format!("{:x}", context.compute()))
 }
 
 async fn download_part(
@@ -100,22 +683,157 @@ async fn download_part(
     opt: Opt,
     start: u64,
     end: u64,
-) -> Result<Vec<u8>, anyhow::Error> {
-    let mut object = client
-        .get_object()
-        .bucket(&opt.bucket)
-        .key(&opt.object)
-        .range(format!("bytes={}-{}", start, end))
-        .send()
-        .await?;
+    file: Arc<Mutex<File>>,
+) -> Result<usize, anyhow::Error> {
+    let description = format!("get_object for bytes={start}-{end}");
+
+    // Retry the send *and* the body drain as one unit: a connection reset or
+    // timeout partway through streaming is the dominant failure mode on a flaky
+    // link, and re-sending the same range then rewriting from `start` (positioned
+    // writes make that idempotent) is what lets the part actually complete.
+    with_retries(opt.max_retries, &description, || async {
+        let mut object = client
+            .get_object()
+            .bucket(&opt.bucket)
+            .key(&opt.object)
+            .range(format!("bytes={}-{}", start, end))
+            .send()
+            .await
+            .map_err(anyhow::Error::from)?;
+
+        let mut offset = start;
+        let mut byte_count = 0_usize;
 
-    let mut out_buf = Vec::with_capacity((end - start) as usize);
+        while let Some(bytes) = object.body.try_next().await? {
+            let mut file = file.lock().await;
+            file.seek(SeekFrom::Start(offset)).await?;
+            file.write_all(&bytes).await?;
 
-    while let Some(bytes) = object.body.try_next().await? {
-        out_buf.extend_from_slice(&bytes);
+            offset += bytes.len() as u64;
+            byte_count += bytes.len();
+        }
+
+        Ok(byte_count)
+    })
+    .await
+}
+
+type AggregateWriter = Arc<Mutex<ZstdEncoder<BufWriter<File>>>>;
+
+// Aggregates a bulk range of a job file's keys into a single zstd-compressed segment,
+// so archival of millions of tiny objects doesn't pay per-object request and storage
+// overhead. Each object is downloaded with the same bounded-concurrency machinery as
+// the multipart download path, then appended, in whatever order it completes, behind
+// a small length-prefixed header so the segment can be split back apart later.
+async fn run_aggregate(client: Client, opt: Opt) -> Result<usize, anyhow::Error> {
+    let job_file = opt
+        .job_file
+        .clone()
+        .ok_or_else(|| anyhow::anyhow!("--aggregate requires --job-file"))?;
+    let start = opt
+        .start_index
+        .ok_or_else(|| anyhow::anyhow!("--aggregate requires --start-index"))?;
+    let end = opt
+        .end_index
+        .ok_or_else(|| anyhow::anyhow!("--aggregate requires --end-index"))?;
+    anyhow::ensure!(start <= end, "start index {start} must be <= end index {end}");
+
+    let keys = read_job_file(&job_file).await?;
+    anyhow::ensure!(
+        (end as usize) <= keys.len(),
+        "end index {end} is out of range for job file with {} keys",
+        keys.len()
+    );
+
+    let output_path = opt.output_dir.join(format!("{start:016x}-{end:016x}"));
+    let file = File::create(&output_path).await?;
+    let writer: AggregateWriter = Arc::new(Mutex::new(ZstdEncoder::new(BufWriter::new(file))));
+
+    let semaphore = Arc::new(Semaphore::new(opt.concurrency));
+    let mut in_flight = FuturesUnordered::new();
+
+    for key in keys[start as usize..end as usize].iter().cloned() {
+        let client = client.clone();
+        let opt = opt.clone();
+        let writer = writer.clone();
+        let semaphore = semaphore.clone();
+
+        in_flight.push(task::spawn(async move {
+            let _permit = semaphore.acquire_owned().await?;
+            download_and_append(client, opt, key, writer).await
+        }));
+    }
+
+    let mut object_count = 0_usize;
+    while let Some(result) = in_flight.next().await {
+        result??;
+        object_count += 1;
     }
 
-    Ok(out_buf)
+    let writer = Arc::try_unwrap(writer)
+        .map_err(|_| anyhow::anyhow!("aggregate writer still has outstanding references"))?
+        .into_inner();
+    writer.shutdown().await?;
+
+    trace!(
+        "aggregated {object_count} objects into {}",
+        output_path.display()
+    );
+
+    Ok(object_count)
+}
+
+async fn download_and_append(
+    client: Client,
+    opt: Opt,
+    key: String,
+    writer: AggregateWriter,
+) -> Result<(), anyhow::Error> {
+    let description = format!("get_object for key {key}");
+    // Retry the send *and* the body drain as one unit, same as `download_part`: a
+    // connection reset partway through the body is the common failure mode, and
+    // since `payload` is rebuilt fresh each attempt, re-sending and redraining from
+    // scratch is safe.
+    let payload = with_retries(opt.max_retries, &description, || async {
+        let mut object = client
+            .get_object()
+            .bucket(&opt.bucket)
+            .key(&key)
+            .send()
+            .await
+            .map_err(anyhow::Error::from)?;
+
+        let mut payload = Vec::new();
+        while let Some(bytes) = object.body.try_next().await? {
+            payload.extend_from_slice(&bytes);
+        }
+
+        Ok(payload)
+    })
+    .await?;
+
+    let key_bytes = key.as_bytes();
+    let mut writer = writer.lock().await;
+    writer
+        .write_all(&(key_bytes.len() as u32).to_le_bytes())
+        .await?;
+    writer.write_all(key_bytes).await?;
+    writer
+        .write_all(&(payload.len() as u64).to_le_bytes())
+        .await?;
+    writer.write_all(&payload).await?;
+
+    Ok(())
+}
+
+async fn read_job_file(path: &Path) -> Result<Vec<String>, anyhow::Error> {
+    let contents = tokio::fs::read_to_string(path).await?;
+    Ok(contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(str::to_owned)
+        .collect())
 }
 
 #[tokio::main(flavor = "multi_thread", worker_threads = 10)]
@@ -128,25 +846,23 @@ async fn main() {
 
     let opts = Opt::parse();
 
-    if opts.multipart {
-        match get_object_multipart(client, opts).await {
-            Ok(bytes) => {
-                println!("Wrote {bytes}");
-            }
-            Err(err) => {
-                eprintln!("Error: {}", err);
-                std::process::exit(1);
-            }
-        }
+    let result = if opts.aggregate {
+        run_aggregate(client, opts).await
+    } else if opts.upload {
+        put_object_multipart(client, opts).await
+    } else if opts.multipart {
+        get_object_multipart(client, opts).await
     } else {
-        match get_object(client, opts).await {
-            Ok(bytes) => {
-                println!("Wrote {bytes}");
-            }
-            Err(err) => {
-                eprintln!("Error: {}", err);
-                std::process::exit(1);
-            }
+        get_object(client, opts).await
+    };
+
+    match result {
+        Ok(bytes) => {
+            println!("Wrote {bytes}");
+        }
+        Err(err) => {
+            eprintln!("Error: {}", err);
+            std::process::exit(1);
         }
     }
 }
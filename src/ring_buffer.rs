@@ -0,0 +1,302 @@
+//! A single-producer/single-consumer FIFO byte ring buffer backed by a "magic"
+//! virtual-memory mapping: the same physical pages are mapped twice, back to back,
+//! so a `reserve` or `pop` that straddles the physical end of the buffer still sees
+//! one contiguous slice instead of wrapping. This removes the copy (or branch and
+//! copy) a naive ring buffer needs whenever its cursors cross the physical end,
+//! which matters on high-bandwidth links where per-chunk allocation and copying
+//! become the bottleneck.
+//!
+//! `capacity` is rounded up to a multiple of the OS page size, since the double
+//! mapping requires page-aligned, page-sized regions.
+
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use tokio::sync::Notify;
+
+/// Creates a mirrored ring buffer and splits it into its producer and consumer
+/// halves. `min_capacity` is rounded up to a page-size multiple.
+pub fn ring_buffer(min_capacity: usize) -> std::io::Result<(Producer, Consumer)> {
+    let inner = Arc::new(Inner::new(min_capacity)?);
+    Ok((
+        Producer {
+            inner: inner.clone(),
+        },
+        Consumer { inner },
+    ))
+}
+
+struct Inner {
+    ptr: *mut u8,
+    capacity: usize,
+    // Monotonically increasing byte offsets; the physical offset is `cursor % capacity`.
+    head: AtomicUsize,
+    tail: AtomicUsize,
+    space_available: Notify,
+    data_available: Notify,
+    closed: AtomicBool,
+}
+
+// `ptr` points at a pair of mmap'd regions that `Inner` owns exclusively; `head`
+// and `tail` are the only state guarding access to them, and both are atomic.
+unsafe impl Send for Inner {}
+unsafe impl Sync for Inner {}
+
+impl Inner {
+    fn new(min_capacity: usize) -> std::io::Result<Self> {
+        let page_size = page_size();
+        let capacity = ((min_capacity + page_size - 1) / page_size * page_size).max(page_size);
+        let ptr = unsafe { map_mirrored(capacity)? };
+
+        Ok(Self {
+            ptr,
+            capacity,
+            head: AtomicUsize::new(0),
+            tail: AtomicUsize::new(0),
+            space_available: Notify::new(),
+            data_available: Notify::new(),
+            closed: AtomicBool::new(false),
+        })
+    }
+}
+
+impl Drop for Inner {
+    fn drop(&mut self) {
+        unsafe {
+            libc::munmap(self.ptr.cast(), self.capacity * 2);
+        }
+    }
+}
+
+/// The writable half of a ring buffer.
+pub struct Producer {
+    inner: Arc<Inner>,
+}
+
+impl Producer {
+    /// Waits until at least `len` bytes are free, then returns a contiguous
+    /// writable view of the next `len` bytes at the current tail. The view is not
+    /// considered pushed until [`Producer::commit`] is called with how many of
+    /// those bytes were actually written.
+    pub async fn reserve(&self, len: usize) -> &mut [u8] {
+        debug_assert!(len <= self.inner.capacity, "reserve exceeds ring capacity");
+
+        loop {
+            // `notified()` doesn't register the waiter until the future is first
+            // polled, so pin it and `enable()` it *before* checking the condition.
+            // Otherwise a `commit` landing between the check and the `.await`
+            // calls `notify_waiters()` while nobody is registered yet, and the
+            // wakeup is lost — this is what was deadlocking the download path.
+            let notified = self.inner.space_available.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+
+            let head = self.inner.head.load(Ordering::Acquire);
+            let tail = self.inner.tail.load(Ordering::Relaxed);
+            let free = self.inner.capacity - (tail - head);
+            if free >= len {
+                let offset = tail % self.inner.capacity;
+                return unsafe { std::slice::from_raw_parts_mut(self.inner.ptr.add(offset), len) };
+            }
+            notified.await;
+        }
+    }
+
+    /// Advances the tail by `len`, making those bytes visible to the consumer.
+    pub fn commit(&self, len: usize) {
+        self.inner.tail.fetch_add(len, Ordering::Release);
+        self.inner.data_available.notify_waiters();
+    }
+
+    /// Marks the stream as finished: once the consumer drains what's already
+    /// queued, further `pop` calls return `None` instead of waiting forever.
+    pub fn close(&self) {
+        self.inner.closed.store(true, Ordering::Release);
+        self.inner.data_available.notify_waiters();
+    }
+}
+
+/// The readable half of a ring buffer.
+pub struct Consumer {
+    inner: Arc<Inner>,
+}
+
+impl Consumer {
+    /// Waits until at least one byte is queued, then returns a contiguous
+    /// readable view of up to `len` bytes at the current head. The bytes stay
+    /// queued until [`Consumer::commit`] is called with how many were consumed.
+    /// Returns `None` once the producer has [`Producer::close`]d the stream and
+    /// every queued byte has been consumed.
+    pub async fn pop(&self, len: usize) -> Option<&[u8]> {
+        loop {
+            // See the matching comment in `Producer::reserve`: pin and `enable()`
+            // the notification before checking, or a `commit`/`close` racing with
+            // the check is lost and this waits forever.
+            let notified = self.inner.data_available.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+
+            let tail = self.inner.tail.load(Ordering::Acquire);
+            let head = self.inner.head.load(Ordering::Relaxed);
+            let available = tail - head;
+            if available > 0 {
+                let len = len.min(available);
+                let offset = head % self.inner.capacity;
+                return Some(unsafe { std::slice::from_raw_parts(self.inner.ptr.add(offset), len) });
+            }
+            if self.inner.closed.load(Ordering::Acquire) {
+                return None;
+            }
+            notified.await;
+        }
+    }
+
+    /// Advances the head by `len`, freeing those bytes up for the producer to reuse.
+    pub fn commit(&self, len: usize) {
+        self.inner.head.fetch_add(len, Ordering::Release);
+        self.inner.space_available.notify_waiters();
+    }
+}
+
+fn page_size() -> usize {
+    unsafe { libc::sysconf(libc::_SC_PAGESIZE) as usize }
+}
+
+/// Reserves `2 * capacity` bytes of address space, then maps the same
+/// anonymous-file-backed pages into both halves, so the two halves mirror
+/// each other and a span crossing the midpoint reads/writes as one contiguous
+/// region of the underlying storage.
+unsafe fn map_mirrored(capacity: usize) -> std::io::Result<*mut u8> {
+    let reservation = libc::mmap(
+        std::ptr::null_mut(),
+        capacity * 2,
+        libc::PROT_NONE,
+        libc::MAP_ANONYMOUS | libc::MAP_PRIVATE,
+        -1,
+        0,
+    );
+    if reservation == libc::MAP_FAILED {
+        return Err(std::io::Error::last_os_error());
+    }
+
+    let name: &[u8] = b"aws_sdk_experiments_ring_buffer\0";
+    let fd = libc::memfd_create(name.as_ptr().cast(), 0);
+    if fd == -1 {
+        libc::munmap(reservation, capacity * 2);
+        return Err(std::io::Error::last_os_error());
+    }
+
+    let result = (|| {
+        if libc::ftruncate(fd, capacity as libc::off_t) == -1 {
+            return Err(std::io::Error::last_os_error());
+        }
+
+        let first = libc::mmap(
+            reservation,
+            capacity,
+            libc::PROT_READ | libc::PROT_WRITE,
+            libc::MAP_SHARED | libc::MAP_FIXED,
+            fd,
+            0,
+        );
+        if first == libc::MAP_FAILED {
+            return Err(std::io::Error::last_os_error());
+        }
+
+        let second = libc::mmap(
+            reservation.add(capacity),
+            capacity,
+            libc::PROT_READ | libc::PROT_WRITE,
+            libc::MAP_SHARED | libc::MAP_FIXED,
+            fd,
+            0,
+        );
+        if second == libc::MAP_FAILED {
+            return Err(std::io::Error::last_os_error());
+        }
+
+        Ok(())
+    })();
+
+    libc::close(fd);
+
+    match result {
+        Ok(()) => Ok(reservation.cast()),
+        Err(err) => {
+            libc::munmap(reservation, capacity * 2);
+            Err(err)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // `ring_buffer(1)` rounds up to exactly one page, so pushing more than a page
+    // forces the write to straddle the mirror boundary (wrap from the first mapping
+    // into the second), which is the whole point of the double mapping.
+    #[tokio::test]
+    async fn push_and_pop_straddles_the_mirror_boundary() {
+        let capacity = page_size();
+        let (producer, consumer) = ring_buffer(1).unwrap();
+
+        let first: Vec<u8> = (0..capacity * 3 / 4).map(|i| i as u8).collect();
+        let second: Vec<u8> = (0..capacity / 2).map(|i| i as u8).collect();
+
+        let slot = producer.reserve(first.len()).await;
+        slot.copy_from_slice(&first);
+        producer.commit(first.len());
+
+        let popped = consumer.pop(first.len()).await.unwrap().to_vec();
+        assert_eq!(popped, first);
+        consumer.commit(popped.len());
+
+        // The tail now sits three quarters of the way into the buffer, so this
+        // reserve runs past the physical end of the backing pages; because of the
+        // mirrored mapping the returned slice is still contiguous even though the
+        // underlying bytes wrap around to the start of those pages.
+        let slot = producer.reserve(second.len()).await;
+        slot.copy_from_slice(&second);
+        producer.commit(second.len());
+
+        let popped = consumer.pop(second.len()).await.unwrap().to_vec();
+        assert_eq!(popped, second);
+        consumer.commit(popped.len());
+    }
+
+    // Regression test for the lost-wakeup deadlock: a producer blocked in `reserve`
+    // on a full buffer must wake once the consumer commits enough bytes to free the
+    // space, even though `commit` may run in the window between the producer's
+    // condition check and its `.await`.
+    #[tokio::test]
+    async fn blocked_producer_is_woken_by_a_draining_consumer() {
+        let capacity = page_size();
+        let (producer, consumer) = ring_buffer(1).unwrap();
+
+        // Fill the buffer completely so the next reserve has to wait.
+        let filler = vec![0_u8; capacity];
+        let slot = producer.reserve(filler.len()).await;
+        slot.copy_from_slice(&filler);
+        producer.commit(filler.len());
+
+        let producer_task = tokio::spawn(async move {
+            let payload = vec![1_u8; 4];
+            let slot = producer.reserve(payload.len()).await;
+            slot.copy_from_slice(&payload);
+            producer.commit(payload.len());
+        });
+
+        // Give the reserve() call above a chance to actually start waiting before
+        // the consumer frees space, so this exercises the wakeup path rather than
+        // the fast path where free space was already available.
+        tokio::task::yield_now().await;
+
+        let popped = consumer.pop(capacity).await.unwrap().len();
+        consumer.commit(popped);
+
+        tokio::time::timeout(std::time::Duration::from_secs(5), producer_task)
+            .await
+            .expect("producer was never woken after space was freed")
+            .unwrap();
+    }
+}